@@ -0,0 +1,107 @@
+//external level/config format, loaded from resources/config.json so designers can tune
+//the game or author a deterministic pillar sequence without recompiling
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use ggez::graphics::Color;
+use ggez::{filesystem, Context};
+
+use serde::Deserialize;
+
+const CONFIG_PATH: &str = "/config.json";
+
+//one scripted pillar: which palette entry to use and how tall the pillar's top half is,
+//i.e. where its gap begins
+#[derive(Deserialize, Clone)]
+pub struct PillarEntry {
+    pub color_index: usize,
+    pub gap_height: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    pub window_width: f32,
+    pub window_height: f32,
+    pub pillar_gap: f32,
+    pub pillar_distance: f32,
+    pub pillar_width: f32,
+    pub pillar_acceleration: f32,
+    pub gravity: f32,
+    pub jump_amount: f32,
+    pub initial_pillar_speed: f32,
+    pub palette: Vec<[f32; 4]>,
+    //an optional scripted sequence of pillars; when present, pillars loop through it
+    //instead of being randomized
+    pub sequence: Option<Vec<PillarEntry>>,
+    //fixes the rng seed so pillar generation (and therefore ghost replay) is reproducible;
+    //overridden by the FLAPPYCUBE_SEED env var, and left unset for a fresh seed each launch
+    pub rng_seed: Option<u64>,
+}
+
+impl Config {
+    //the values that used to be hard-coded consts, kept as the fallback
+    fn defaults() -> Self {
+        Config {
+            window_width: 800.0,
+            window_height: 600.0,
+            pillar_gap: 220.0,
+            pillar_distance: 300.0,
+            pillar_width: 80.0,
+            pillar_acceleration: -0.0004,
+            gravity: 1.0,
+            jump_amount: -10.0,
+            initial_pillar_speed: -1.0,
+            palette: vec![
+                [0.13725491, 0.23921569, 0.3019608, 1.0],
+                [0.99607843, 0.49803922, 0.1764706, 1.0],
+                [0.9882353, 0.7921569, 0.27450982, 1.0],
+                [0.6313726, 0.75686276, 0.5058824, 1.0],
+                [0.38039216, 0.60784316, 0.5411765, 1.0],
+            ],
+            sequence: None,
+            rng_seed: None,
+        }
+    }
+
+    //loads resources/config.json, falling back to the defaults if it's missing or invalid
+    pub fn load(ctx: &mut Context) -> Self {
+        let mut contents = String::new();
+        let loaded = filesystem::open(ctx, CONFIG_PATH)
+            .ok()
+            .and_then(|mut file| file.read_to_string(&mut contents).ok())
+            .and_then(|_| serde_json::from_str(&contents).ok());
+        let mut config: Config = loaded.unwrap_or_else(Config::defaults);
+        //an empty sequence is indistinguishable from "no sequence" to every caller, and
+        //would otherwise panic on a modulo by zero the first time a pillar wraps
+        if config.sequence.as_ref().map_or(false, |s| s.is_empty()) {
+            config.sequence = None;
+        }
+        //an empty palette would panic the same way in pick_pillar_color/build_player/
+        //build_pillars (rng.gen_range(0, 0)), so fall back to the default palette instead
+        if config.palette.is_empty() {
+            config.palette = Config::defaults().palette;
+        }
+        config
+    }
+
+    //peeks at the window dimensions in resources/config.json straight from disk, since the
+    //game window has to be built before ggez's Context exists, and Config::load needs a
+    //Context to read through ggez's own filesystem abstraction
+    pub fn peek_window_size(resource_dir: &Path) -> (f32, f32) {
+        let loaded: Option<Config> = fs::read_to_string(resource_dir.join("config.json"))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        let config = loaded.unwrap_or_else(Config::defaults);
+        (config.window_width, config.window_height)
+    }
+
+    //converts the palette entries into ggez colors
+    pub fn colors(&self) -> Vec<Color> {
+        self.palette
+            .iter()
+            .map(|c| Color::new(c[0], c[1], c[2], c[3]))
+            .collect()
+    }
+}
@@ -0,0 +1,138 @@
+//live debug/tuning overlay, toggled with F1, built with imgui
+
+use gfx::format::Srgba8;
+use imgui::{Condition, Context as ImguiContext, FontSource, Ui};
+use imgui_gfx_renderer::{Renderer, Shaders};
+use imgui_winit_support::{HiDpiMode, WinitPlatform};
+
+use ggez::graphics::{self, Color};
+use ggez::Context;
+
+use crate::config::Config;
+use crate::{Pillar, Player};
+
+pub struct DebugOverlay {
+    imgui: ImguiContext,
+    platform: WinitPlatform,
+    //Srgba8 is the pixel format ggez's gfx backend renders to, not a draw-color value type
+    renderer: Renderer<Srgba8, gfx_device_gl::Resources>,
+    visible: bool,
+}
+
+impl DebugOverlay {
+    pub fn new(ctx: &mut Context) -> Self {
+        let mut imgui = ImguiContext::create();
+        imgui.set_ini_filename(None);
+        imgui
+            .fonts()
+            .add_font(&[FontSource::DefaultFontData { config: None }]);
+
+        let mut platform = WinitPlatform::init(&mut imgui);
+        platform.attach_window(imgui.io_mut(), graphics::window(ctx), HiDpiMode::Default);
+
+        let (factory, device, _, _, _) = graphics::gfx_objects(ctx);
+        let shaders = Shaders::GlSl150;
+        let renderer = Renderer::init(&mut imgui, factory, device, shaders).unwrap();
+
+        DebugOverlay {
+            imgui,
+            platform,
+            renderer,
+            visible: false,
+        }
+    }
+
+    //F1 flips whether the overlay is drawn; mouse input is fed to imgui either way
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    //feeds the current mouse position into imgui so sliders can be dragged
+    pub fn handle_mouse_motion(&mut self, x: f32, y: f32) {
+        self.imgui.io_mut().mouse_pos = [x, y];
+    }
+
+    //feeds a mouse button edge into imgui
+    pub fn handle_mouse_button(&mut self, button: ggez::input::mouse::MouseButton, pressed: bool) {
+        let index = match button {
+            ggez::input::mouse::MouseButton::Left => 0,
+            ggez::input::mouse::MouseButton::Right => 1,
+            ggez::input::mouse::MouseButton::Middle => 2,
+            _ => return,
+        };
+        self.imgui.io_mut().mouse_down[index] = pressed;
+    }
+
+    //renders the live state readout and the sliders that tune the config in place
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        config: &mut Config,
+        player: &Player,
+        pillars: &[Pillar],
+        pillar_speed: &mut f32,
+        score: u32,
+    ) -> ggez::GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        self.platform
+            .prepare_frame(self.imgui.io_mut(), graphics::window(ctx))
+            .unwrap();
+        let ui: Ui = self.imgui.frame();
+
+        imgui::Window::new("Debug")
+            .size([320.0, 360.0], Condition::FirstUseEver)
+            .build(&ui, || {
+                ui.text(format!("velocity: ({:.2}, {:.2})", player.velocity.x, player.velocity.y));
+                ui.text(format!("pillar_speed: {:.4}", *pillar_speed));
+                ui.text(format!("score: {}", score));
+                ui.separator();
+                for (i, pillar) in pillars.iter().enumerate() {
+                    ui.text(format!(
+                        "pillar {}: top={:?} bottom={:?}",
+                        i, pillar.top, pillar.bottom
+                    ));
+                }
+                ui.separator();
+                imgui::Slider::new("gravity", 0.0, 3.0).build(&ui, &mut config.gravity);
+                imgui::Slider::new("jump_amount", -20.0, -1.0).build(&ui, &mut config.jump_amount);
+                imgui::Slider::new("pillar_gap", 100.0, 400.0).build(&ui, &mut config.pillar_gap);
+                imgui::Slider::new("pillar_acceleration", -0.002, 0.0)
+                    .build(&ui, &mut config.pillar_acceleration);
+                //bound to the live speed itself (not config.initial_pillar_speed, which is
+                //only read again on the next reset) so dragging it takes effect immediately
+                imgui::Slider::new("spawn speed", -5.0, -0.2).build(&ui, &mut *pillar_speed);
+            });
+
+        self.platform.prepare_render(&ui, graphics::window(ctx));
+        let draw_data = ui.render();
+        let (factory, _, encoder, _, render_target) = graphics::gfx_objects(ctx);
+        self.renderer
+            .render(factory, encoder, &mut graphics::RenderTargetView::clone(render_target), draw_data)
+            .unwrap();
+        Ok(())
+    }
+
+    //draws the collision rectangles used by collide_rect, while the overlay is open
+    pub fn draw_collision_outlines(&self, ctx: &mut Context, pillars: &[Pillar], player: &Player) -> ggez::GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+        let outline_color = Color::new(1.0, 0.0, 0.0, 0.6);
+        for pillar in pillars {
+            for rect in [pillar.top, pillar.bottom] {
+                let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(1.0), rect, outline_color)?;
+                graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+            }
+        }
+        let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(1.0), player.body, outline_color)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+        Ok(())
+    }
+}
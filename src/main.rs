@@ -2,6 +2,13 @@
 
 extern crate ggez;
 
+mod config;
+mod debug_overlay;
+mod particles;
+mod replay;
+mod scene;
+mod synth;
+
 use ggez::*;
 use ggez::input::keyboard::{KeyCode, KeyMods};
 use ggez::audio::SoundSource;
@@ -10,31 +17,27 @@ use ggez::graphics::Color;
 use std::env;
 use std::path;
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-//constants related to the game
-const WINDOW_WIDTH: f32 = 800.0;
-const WINDOW_HEIGHT: f32 = 600.0;
+use config::Config;
+use debug_overlay::DebugOverlay;
+use particles::Particles;
+use replay::{Ghost, InputKind, Recorder};
+use scene::Scene;
+use synth::Synth;
 
 //the framerate we want
 const DESIRED_FPS: u32 = 60;
 
 //the number of pillars which will be active during the game
 const NUM_PILLARS: u32 = 5;
-//the gap/opening between two blocks of a pillar
-const PILLAR_GAP: f32 = 220.0;
-//distance between two pillars
-const PILLAR_DISTANCE: f32 = 300.0;
-//width of a pillar block
-const PILLAR_WIDTH: f32 = 80.0;
-//the amount by which the speed of the pillars increases every frame
-const PILLAR_ACCELERATION: f32 = -0.0004;
 
-//the gravity in the game 
-const GRAVITY: f32 = 1.0;
+//how close the player's mixed color needs to be to a pillar's color to count as a match
+const COLOR_MATCH_EPSILON: f32 = 0.05;
 
-//the amount the player jumps 
-const JUMP_AMOUNT: f32 = -10.0;
+//chance that a freshly generated pillar gets a "mix target" color instead of a palette color
+const MIX_TARGET_CHANCE: f32 = 0.3;
 
 //utility function which checks if two rects are colliding
 fn collide_rect(rect1: &graphics::Rect, rect2: &graphics::Rect) -> bool {
@@ -45,21 +48,59 @@ fn collide_rect(rect1: &graphics::Rect, rect2: &graphics::Rect) -> bool {
     (rect1.x < rect2right) && (rect1right > rect2.x) && (rect1.y < rect2bottom) && (rect1bottom > rect2.y)
 }
 
-//structure which stores all the sounds of the game 
-struct Sounds {
-    jump: audio::Source,
-    switch: audio::Source,
-    clink: audio::Source,
-    crash: audio::Source,
+//component-wise average of two colors, clamped to [0,1]
+fn mix_colors(a: Color, b: Color) -> Color {
+    Color::new(
+        ((a.r + b.r) / 2.0).min(1.0).max(0.0),
+        ((a.g + b.g) / 2.0).min(1.0).max(0.0),
+        ((a.b + b.b) / 2.0).min(1.0).max(0.0),
+        1.0,
+    )
+}
+
+//checks whether two colors are within COLOR_MATCH_EPSILON of each other per channel
+fn colors_match(a: &Color, b: &Color) -> bool {
+    (a.r - b.r).abs() < COLOR_MATCH_EPSILON
+        && (a.g - b.g).abs() < COLOR_MATCH_EPSILON
+        && (a.b - b.b).abs() < COLOR_MATCH_EPSILON
+}
+
+//picks a pillar color: usually a single palette entry, occasionally a mix of two
+fn pick_pillar_color(colors_list: &Vec<Color>, rng: &mut impl Rng) -> Color {
+    if rng.gen_range(0.0, 1.0) < MIX_TARGET_CHANCE {
+        let a = colors_list[rng.gen_range(0, colors_list.len())];
+        let b = colors_list[rng.gen_range(0, colors_list.len())];
+        mix_colors(a, b)
+    } else {
+        colors_list[rng.gen_range(0, colors_list.len())]
+    }
+}
+
+//picks the color and gap-top height for the next pillar: pulled from the scripted
+//sequence if one is configured (continuing wherever sequence_index left off), otherwise
+//randomized. Shared by the initial pillar build and the wrap-around in Pillar::update so
+//a scripted level is scripted from the very first pillar onward, not just after it wraps
+fn next_pillar_spec(config: &Config, colors_list: &Vec<Color>, sequence_index: &mut usize, rng: &mut impl Rng) -> (Color, f32) {
+    match &config.sequence {
+        Some(sequence) => {
+            let entry = &sequence[*sequence_index % sequence.len()];
+            *sequence_index += 1;
+            (colors_list[entry.color_index % colors_list.len()], entry.gap_height)
+        }
+        None => (
+            pick_pillar_color(colors_list, rng),
+            rng.gen_range(0.0, config.window_height - config.pillar_gap),
+        ),
+    }
 }
 
 //structure which stores all the text elements of the game
 struct Texts {
     intro: graphics::Text,
     intro_pos: mint::Point2<f32>,
-    intro_offscreen: bool,
     score: graphics::Text,
     restart: graphics::Text,
+    paused: graphics::Text,
 }
 
 //the pillar struct which contains the properties such as color and dimensions
@@ -72,23 +113,22 @@ struct Pillar {
 
 //methods for updating and drawing the pillars
 impl Pillar {
-    fn update(&mut self, colors_list: &Vec<Color>, last_x: f32, speed: f32) -> GameResult {
+    fn update(&mut self, colors_list: &Vec<Color>, config: &Config, sequence_index: &mut usize, last_x: f32, speed: f32, rng: &mut impl Rng) -> GameResult {
         //make the pillars move to the left
         self.top.x += speed;
         self.bottom.x += speed;
         //check if pillars cross the screen
-        if self.top.x + PILLAR_WIDTH <= 0.0 {
+        if self.top.x + config.pillar_width <= 0.0 {
             //wrap them back to the right
-            self.top.x = last_x + PILLAR_DISTANCE;
-            self.bottom.x = last_x + PILLAR_DISTANCE;
-            //give them a new color
-            self.color = colors_list[rand::thread_rng().gen_range(0, colors_list.len())];
-            //give them a new height 
-            let height = rand::thread_rng().gen_range(0.0, WINDOW_HEIGHT - PILLAR_GAP);
+            self.top.x = last_x + config.pillar_distance;
+            self.bottom.x = last_x + config.pillar_distance;
+            let (color, height) = next_pillar_spec(config, colors_list, sequence_index, rng);
+            //give them a new color, occasionally a mix target that forces the player to combine
+            self.color = color;
             //adjust the dimensions of the pillars to match the new height
             self.top.h = height;
-            self.bottom.y = height + PILLAR_GAP;
-            self.bottom.h = WINDOW_HEIGHT - (height + PILLAR_GAP);
+            self.bottom.y = height + config.pillar_gap;
+            self.bottom.h = config.window_height - (height + config.pillar_gap);
         }
         //return
         Ok(())
@@ -116,30 +156,45 @@ impl Pillar {
     }
 }
 
-//the struct which stores properties of the player 
+//the struct which stores properties of the player
 struct Player {
     color_index: usize,
-    color: Color, 
+    color_index2: usize,
+    //which slot (0 or 1) the Ctrl switch currently cycles
+    active_slot: usize,
+    color: Color,
+    color2: Color,
+    //component-wise average of color and color2, clamped to [0,1]
+    mixed_color: Color,
     body: graphics::Rect,
     velocity: mint::Point2<f32>,
 }
 
 //methods for updating and drawing the player
 impl Player {
-    fn update(&mut self, pillars: &mut Vec<Pillar>, game_over: &mut bool, sounds: &mut Sounds, score: &mut u32) -> GameResult {
+    fn update(&mut self, pillars: &mut Vec<Pillar>, config: &Config, game_over: &mut bool, synth: &mut Synth, particles: &mut Particles, score: &mut u32) -> GameResult {
+        //recompute the mixed color from the two active slots
+        self.mixed_color = mix_colors(self.color, self.color2);
+        //center point of the player, used as the origin for particle emissions
+        let center = mint::Point2 {
+            x: self.body.x + self.body.w / 2.0,
+            y: self.body.y + self.body.h / 2.0,
+        };
         //check if player is inside/below/above any of the pillars
         for pillar in pillars {
             if self.body.x < pillar.top.x + pillar.top.w && self.body.x + self.body.w > pillar.top.x {
-                if pillar.color != self.color {
+                if !colors_match(&pillar.color, &self.mixed_color) {
                     //DEBUG-REMOVE
                     //println!("full collision not checked - WRONG COLOR");
-                    let _ = sounds.crash.play();
+                    synth.trigger(synth::Event::Crash);
+                    particles.emit_crash(center, self.mixed_color);
                     *game_over = true;
                 }
                 else if collide_rect(&self.body, &pillar.top) || collide_rect(&self.body, &pillar.bottom) {
                     //DEBUG-REMOVE
                     //println!("CORRECT COLOR BUT U COLLIDE MAN");
-                    let _ = sounds.crash.play();
+                    synth.trigger(synth::Event::Crash);
+                    particles.emit_crash(center, self.mixed_color);
                     *game_over = true;
                 }
                 //check if middle parts (horizontal) align
@@ -148,150 +203,276 @@ impl Player {
                     //println!("clink!");
                     //increment score
                     *score += 1;
-                    let _ = sounds.clink.play();
+                    synth.trigger(synth::Event::Clink);
+                    particles.emit_clink(center, pillar.color);
                 }
             }
         }
         //add gravity to player's velocity and accelerate the player
-        self.velocity.y += GRAVITY;
+        self.velocity.y += config.gravity;
         self.body.y += self.velocity.y;
         //if the player is about ot go off screen, negate the added gravity
-        if self.body.y + self.body.h >= WINDOW_HEIGHT {
-            self.velocity.y = -GRAVITY;
+        if self.body.y + self.body.h >= config.window_height {
+            self.velocity.y = -config.gravity;
         }
         Ok(())
     }
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        //create a drawable mesh for the player 
-        let player_mesh = graphics::Mesh::new_rectangle(
-            ctx, 
-            graphics::DrawMode::fill(),
+        //split the body in half to show each active color, slot 0 on the left
+        let left_half = graphics::Rect::new(self.body.x, self.body.y, self.body.w / 2.0, self.body.h);
+        let right_half = graphics::Rect::new(self.body.x + self.body.w / 2.0, self.body.y, self.body.w / 2.0, self.body.h);
+        let left_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), left_half, self.color)?;
+        let right_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), right_half, self.color2)?;
+        graphics::draw(ctx, &left_mesh, graphics::DrawParam::default())?;
+        graphics::draw(ctx, &right_mesh, graphics::DrawParam::default())?;
+        //outline the body in the mixed color so the blend being checked against pillars is visible
+        let outline_mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
             self.body,
-            self.color,
+            self.mixed_color,
         )?;
-        //draw the player
-        graphics::draw(ctx, &player_mesh, graphics::DrawParam::default())?;
+        graphics::draw(ctx, &outline_mesh, graphics::DrawParam::default())?;
         Ok(())
     }
 }
 
+//builds a fresh vector of pillars from scratch, used both on startup and on reset
+fn build_pillars(config: &Config, colors_list: &Vec<Color>, sequence_index: &mut usize, rng: &mut impl Rng) -> Vec<Pillar> {
+    let mut pv = Vec::new();
+    for i in 0..NUM_PILLARS {
+        let (color, height) = next_pillar_spec(config, colors_list, sequence_index, rng);
+        pv.push(Pillar {
+            color,
+            //the top block/half of the pillar
+            top: graphics::Rect::new(
+                //x
+                config.window_width + config.pillar_distance * i as f32,
+                //y
+                0.0,
+                //width
+                config.pillar_width,
+                //height
+                height,
+            ),
+            bottom: graphics::Rect::new(
+                //x
+                config.window_width + config.pillar_distance * i as f32,
+                //y
+                height + config.pillar_gap,
+                //width
+                config.pillar_width,
+                //height
+                config.window_height - (height + config.pillar_gap),
+            ),
+        });
+    }
+    pv
+}
+
+//builds a fresh player in the middle of the screen, used both on startup and on reset
+fn build_player(config: &Config, colors_list: &Vec<Color>, rng: &mut impl Rng) -> Player {
+    let color_index: usize = rng.gen_range(0, colors_list.len());
+    let color_index2: usize = rng.gen_range(0, colors_list.len());
+    Player {
+        color_index: color_index,
+        color_index2: color_index2,
+        active_slot: 0,
+        color: colors_list[color_index],
+        color2: colors_list[color_index2],
+        mixed_color: mix_colors(colors_list[color_index], colors_list[color_index2]),
+        body: graphics::Rect::new(
+            //x
+            config.window_width / 2.0,
+            //y
+            config.window_height / 2.0,
+            //width
+            50.0,
+            //height
+            50.0,
+        ),
+        //the velocity of the player
+        velocity: mint::Point2 {
+            x: 0.0,
+            y: 0.0,
+        },
+    }
+}
+
+//the player-facing actions that both the keyboard and a gamepad can trigger, so the
+//sound/velocity/recording logic behind each only has to live in one place
+enum Action {
+    Jump,
+    Switch,
+    ToggleSlot,
+    Restart,
+}
+
 //the main state of the game
 struct MainState {
+    config: Config,
     colors: Vec<Color>,
     player: Player,
     pillars: Vec<Pillar>,
     pillar_speed: f32,
-    game_over: bool,
-    sounds: Sounds,
+    //how far along the config's scripted pillar sequence we are, if one was loaded
+    sequence_index: usize,
+    scene: Scene,
+    synth: Synth,
+    particles: Particles,
+    debug_overlay: DebugOverlay,
     score: u32,
+    //the highest score reached across resets, shown on the game-over scene
+    best_score: u32,
     texts: Texts,
+    //fixed seed reapplied on every reset, so pillar generation is identical run to run
+    seed: u64,
+    rng: StdRng,
+    //records the current run's Space/Ctrl presses for next run's ghost
+    recorder: Recorder,
+    //the previous run's input stream plus the config that was live while it was recorded,
+    //replayed as a ghost on the run that follows it
+    last_run: Option<(Config, Vec<replay::InputEvent>)>,
+    ghost: Option<Ghost>,
 }
 
 impl MainState {
     fn new(ctx: &mut Context) -> Self {
-        //random number generator 
-        let mut rng = rand::thread_rng();
+        //load tuning knobs and an optional scripted level from resources/config.json
+        let config = Config::load(ctx);
+        //the seed behind every deterministic run: env var takes priority, then the config,
+        //otherwise a fresh one is drawn so unconfigured games still vary between launches
+        let seed = env::var("FLAPPYCUBE_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .or(config.rng_seed)
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
         //color scheme used in the game
-        let colors_list: Vec<Color> = vec![
-            [0.13725491, 0.23921569, 0.3019608, 1.0].into(),
-            [0.99607843, 0.49803922, 0.1764706, 1.0].into(),
-            [0.9882353, 0.7921569, 0.27450982, 1.0].into(),
-            [0.6313726, 0.75686276, 0.5058824, 1.0].into(),
-            [0.38039216, 0.60784316, 0.5411765, 1.0].into(),
-        ];
-        //create sounds
-        let mut clink_sound = audio::Source::new(ctx, "/clink.wav").unwrap();
-        let mut jump_sound = audio::Source::new(ctx, "/perc.wav").unwrap();
-        jump_sound.set_volume(0.6);
-        let mut crash_sound = audio::Source::new(ctx, "/crash.wav").unwrap();
-        let mut switch_sound = audio::Source::new(ctx, "/switch.wav").unwrap();
-        //create score text 
+        let colors_list: Vec<Color> = config.colors();
+        //synth that generates sound effects at runtime instead of loading .wav assets
+        let synth = Synth::new();
+        //create score text
         let font = graphics::Font::new(ctx, "/Raleway-Black.ttf").unwrap();
         let score_text = graphics::Text::new(("0", font, 40.0));
-        let intro_text = graphics::Text::new(("Space to jump\nCtrl to switch colors", font, 30.0));
-        let restart_text = graphics::Text::new(("Oof! Press Enter to restart", font, 30.0));
-        //create a vector of pillars
-        let mut pv = Vec::new();
-        for i in 0..NUM_PILLARS {
-            let height: f32 = rng.gen_range(0.0, WINDOW_HEIGHT - PILLAR_GAP);
-            pv.push(Pillar {
-                //the color of the pillar
-                color: colors_list[rng.gen_range(0, colors_list.len())],
-                //the top block/half of the pillar
-                top: graphics::Rect::new(
-                    //x
-                    WINDOW_WIDTH + PILLAR_DISTANCE * i as f32,
-                    //y
-                    0.0,
-                    //width
-                    PILLAR_WIDTH,
-                    //height
-                    height,
-                ),
-                bottom: graphics::Rect::new(
-                    //x
-                    WINDOW_WIDTH + PILLAR_DISTANCE * i as f32,
-                    //y
-                    height + PILLAR_GAP,
-                    //width
-                    PILLAR_WIDTH,
-                    //height
-                    WINDOW_HEIGHT - (height + PILLAR_GAP),
-                ),
-            });
-        }
-        //stores the color index for the player 
-        let color_index: usize = rng.gen_range(0, colors_list.len());
+        let intro_text = graphics::Text::new(("Space to start\nSpace to jump, Ctrl to switch colors, Tab to pick a slot, Esc to pause", font, 30.0));
+        let restart_text = graphics::Text::new(("Oof! Press Enter to go back to the menu", font, 30.0));
+        let paused_text = graphics::Text::new(("Paused - Esc to resume", font, 30.0));
+        //the initial playfield; consults config.sequence too, so a scripted level is
+        //scripted from the first pillar rather than only once pillars start wrapping
+        let mut sequence_index: usize = 0;
+        let pillars = build_pillars(&config, &colors_list, &mut sequence_index, &mut rng);
+        let player = build_player(&config, &colors_list, &mut rng);
+        //initial speed and window size, pulled from config before it's moved into MainState
+        let pillar_speed = config.initial_pillar_speed;
+        let window_width = config.window_width;
+        let window_height = config.window_height;
         //return a MainState
         MainState {
+            config: config,
             colors: colors_list.clone(),
             //the player object
-            player: Player {
-                color_index: color_index,
-                color: colors_list[color_index],
-                body: graphics::Rect::new(
-                    //x
-                    WINDOW_WIDTH / 2.0,
-                    //y
-                    WINDOW_HEIGHT / 2.0,
-                    //width
-                    50.0,
-                    //height
-                    50.0,
-                ),
-                //the velocity of the player 
-                velocity: mint::Point2 {
-                    x: 0.0,
-                    y: 0.0,
-                },
-            },
+            player: player,
             //the vector of pillars
-            pillars: pv,
-            pillar_speed: -1.0,
-            game_over: false,
-            sounds: Sounds {
-                jump: jump_sound,
-                switch: switch_sound,
-                clink: clink_sound,
-                crash: crash_sound,
-            },
+            pillars: pillars,
+            pillar_speed: pillar_speed,
+            sequence_index: sequence_index,
+            scene: Scene::Menu,
+            synth: synth,
+            particles: Particles::new(),
+            debug_overlay: DebugOverlay::new(ctx),
             //player's score
             score: 0,
+            best_score: 0,
             texts: Texts {
                 intro: intro_text,
                 intro_pos: mint::Point2 {
-                    x: WINDOW_WIDTH / 3.0,
-                    y: WINDOW_HEIGHT / 2.2,
+                    x: window_width / 3.0,
+                    y: window_height / 2.2,
                 },
-                intro_offscreen: false,
                 score: score_text,
                 restart: restart_text,
-            }
+                paused: paused_text,
+            },
+            seed: seed,
+            rng: rng,
+            recorder: Recorder::new(),
+            last_run: None,
+            ghost: None,
         }
     }
-    //resets the game 
-    fn reset(&mut self, ctx: &mut Context) {
-        *self = MainState::new(ctx);
+    //rebuilds the playfield (pillars, player, score) in place and transitions to the given scene,
+    //rather than rebuilding the whole struct (and re-loading config/fonts/the debug overlay)
+    fn reset(&mut self, scene: Scene) {
+        //reseeding from the stored seed keeps every run's pillars identical, so a ghost
+        //replayed from an earlier attempt still faces the same playfield
+        self.rng = StdRng::seed_from_u64(self.seed);
+        //reset before building, since build_pillars consumes the sequence from the start
+        self.sequence_index = 0;
+        self.pillars = build_pillars(&self.config, &self.colors, &mut self.sequence_index, &mut self.rng);
+        self.player = build_player(&self.config, &self.colors, &mut self.rng);
+        self.pillar_speed = self.config.initial_pillar_speed;
+        self.score = 0;
+        self.scene = scene;
+        //otherwise a crash burst that hasn't finished decaying keeps animating over the menu
+        self.particles = Particles::new();
+        self.ghost = self.last_run.clone().map(|(config, events)| Ghost::new(config, events));
+    }
+    //shared behind Space/Ctrl/Return and their gamepad equivalents, so jumping, switching
+    //colors, and restarting only have one implementation each regardless of input device
+    fn handle_action(&mut self, action: Action, ctx: &mut Context) {
+        match action {
+            Action::Jump => {
+                if self.scene == Scene::Playing {
+                    //make the player jump by adding negative velocity
+                    self.player.velocity.y = self.config.jump_amount;
+                    //trigger jump sound
+                    self.synth.trigger(synth::Event::Jump);
+                    if let Err(e) = self.synth.play_triggered(ctx) {
+                        eprintln!("failed to play jump sound: {}", e);
+                    }
+                    //burst of particles at the player's position
+                    self.particles.emit_jump(mint::Point2 {
+                        x: self.player.body.x + self.player.body.w / 2.0,
+                        y: self.player.body.y + self.player.body.h,
+                    });
+                    //log the jump so it can be replayed as a ghost on the next run
+                    self.recorder.record(InputKind::Jump);
+                }
+            }
+            Action::Switch => {
+                if self.scene == Scene::Playing {
+                    //cycle whichever slot is currently selected by the Tab toggle
+                    let new_index = if self.player.active_slot == 0 {
+                        self.player.color_index = (self.player.color_index + 1) % self.colors.len();
+                        self.player.color = self.colors[self.player.color_index];
+                        self.player.color_index
+                    } else {
+                        self.player.color_index2 = (self.player.color_index2 + 1) % self.colors.len();
+                        self.player.color2 = self.colors[self.player.color_index2];
+                        self.player.color_index2
+                    };
+                    //trigger switch sound, keyed to the new color index
+                    self.synth.trigger(synth::Event::Switch { color_index: new_index });
+                    if let Err(e) = self.synth.play_triggered(ctx) {
+                        eprintln!("failed to play switch sound: {}", e);
+                    }
+                    //log the switch so it can be replayed as a ghost on the next run
+                    self.recorder.record(InputKind::Switch);
+                }
+            }
+            Action::ToggleSlot => {
+                if self.scene == Scene::Playing {
+                    //toggle which color slot the Switch action cycles
+                    self.player.active_slot = 1 - self.player.active_slot;
+                }
+            }
+            Action::Restart => {
+                if self.scene == Scene::GameOver {
+                    self.reset(Scene::Menu);
+                }
+            }
+        }
     }
 }
 
@@ -301,36 +482,56 @@ impl event::EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
         //make sure the game runs at 60fps
         while timer::check_update_time(ctx, DESIRED_FPS) {
-            //only update if the game is not over
-            if !self.game_over {
+            //only the playing scene runs pillar/player physics; paused freezes them in place
+            //and menu/game-over have no playfield to update yet/anymore
+            if self.scene == Scene::Playing {
+                //advance the frame counter that recorded inputs and the ghost are keyed to
+                self.recorder.tick();
+                if let Some(ghost) = &mut self.ghost {
+                    ghost.update();
+                    if ghost.finished() {
+                        self.ghost = None;
+                    }
+                }
                 //increase speed of the pillars
-                self.pillar_speed += PILLAR_ACCELERATION;
+                self.pillar_speed += self.config.pillar_acceleration;
                 //update the pillars
                 let mut i: usize = 0;
                 let pillars = self.pillars.clone();
                 for pillar in &mut self.pillars {
-                    //calculate the position of the pillar which is in front of the current pillar 
+                    //calculate the position of the pillar which is in front of the current pillar
                     let last_x: f32 = if i == 0 {
                         pillars[pillars.len() - 1].top.x
                     }
                     else {
                         pillars[i - 1].top.x
                     };
-                    pillar.update(&self.colors, last_x, self.pillar_speed)?;
+                    pillar.update(&self.colors, &self.config, &mut self.sequence_index, last_x, self.pillar_speed, &mut self.rng)?;
                     i+=1;
                 }
                 //update the player
-                self.player.update(&mut self.pillars, &mut self.game_over, &mut self.sounds, &mut self.score)?;
+                let mut crashed = false;
+                self.player.update(&mut self.pillars, &self.config, &mut crashed, &mut self.synth, &mut self.particles, &mut self.score)?;
+                //play back whatever the player's update triggered this frame (clink/crash)
+                if let Err(e) = self.synth.play_triggered(ctx) {
+                    eprintln!("failed to play sound: {}", e);
+                }
+                if crashed {
+                    self.best_score = self.best_score.max(self.score);
+                    self.texts.restart.fragments_mut()[0].text = format!(
+                        "Oof! Score: {} (best: {})\nPress Enter for the menu",
+                        self.score, self.best_score
+                    );
+                    self.scene = Scene::GameOver;
+                    //hand this run's input stream off to be replayed as a ghost next time, snapshotting
+                    //the config as it stood for this run so a later config edit can't affect it
+                    self.last_run = Some((self.config.clone(), self.recorder.take()));
+                }
                 //update the score text to the score
                 self.texts.score.fragments_mut()[0].text = self.score.to_string();
-                //update the position of the intro text 
-                if !self.texts.intro_offscreen {
-                    self.texts.intro_pos.x += self.pillar_speed;
-                    if self.texts.intro_pos.x + self.texts.intro.width(ctx) as f32 <= 0.0 {
-                        self.texts.intro_offscreen = true;
-                    }
-                }
             }
+            //particles keep integrating regardless of scene, so bursts finish playing out
+            self.particles.update();
         }
         Ok(())
     }
@@ -338,61 +539,135 @@ impl event::EventHandler for MainState {
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         //clear the screen
         graphics::clear(ctx, graphics::WHITE);
-        //draw the intro text if it is not off screen
-        if !self.texts.intro_offscreen {
+        //the menu scene is just the intro text on an empty screen, before the first pillar spawns
+        if self.scene == Scene::Menu {
             graphics::draw(ctx, &self.texts.intro, graphics::DrawParam::default()
                 .dest(self.texts.intro_pos)
                 .color(graphics::BLACK)
             )?;
         }
-        //draw the pillars
-        for pillar in &mut self.pillars {
-            pillar.draw(ctx)?;
+        //playing and paused both show the frozen-or-moving playfield
+        if self.scene == Scene::Playing || self.scene == Scene::Paused || self.scene == Scene::GameOver {
+            //draw the pillars
+            for pillar in &mut self.pillars {
+                pillar.draw(ctx)?;
+            }
+            //draw the player
+            self.player.draw(ctx)?;
+            //draw the particles
+            self.particles.draw(ctx)?;
+            //draw the previous run's ghost, if one is still replaying
+            if let Some(ghost) = &self.ghost {
+                ghost.draw(ctx)?;
+            }
+            //draw the score
+            graphics::draw(ctx, &self.texts.score, graphics::DrawParam::default().dest(mint::Point2 {
+                x: 20.0,
+                y: 20.0,
+            }).color(graphics::BLACK))?;
         }
-        //draw the player 
-        self.player.draw(ctx)?;
-        //draw the text
-        graphics::draw(ctx, &self.texts.score, graphics::DrawParam::default().dest(mint::Point2 {
-            x: 20.0,
-            y: 20.0,
-        }).color(graphics::BLACK))?;
-        //if the player lost display game over text 
-        if self.game_over {
+        if self.scene == Scene::Paused {
+            graphics::draw(ctx, &self.texts.paused, graphics::DrawParam::default()
+                .dest(self.texts.intro_pos)
+                .color(graphics::BLACK)
+            )?;
+        }
+        //if the player lost display the game over text with the last and best score
+        if self.scene == Scene::GameOver {
             graphics::draw(ctx, &self.texts.restart, graphics::DrawParam::default().dest(mint::Point2 {
-                x: WINDOW_WIDTH/3.0,
-                y: WINDOW_HEIGHT/2.2,
+                x: self.config.window_width/3.0,
+                y: self.config.window_height/2.2,
             }).color(graphics::BLACK))?;
         }
+        //while the debug overlay is open, outline the collision rects and draw the overlay itself
+        self.debug_overlay.draw_collision_outlines(ctx, &self.pillars, &self.player)?;
+        self.debug_overlay.draw(
+            ctx,
+            &mut self.config,
+            &self.player,
+            &self.pillars,
+            &mut self.pillar_speed,
+            self.score,
+        )?;
         //display the stuff that was drawn
         graphics::present(ctx)?;
         Ok(())
     }
     //when a key is pressed
     fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
-        if !self.game_over {
-            //if the user pressed space
-            if keycode == KeyCode::Space {
-                //make the player jump by adding negative velocity
-                self.player.velocity.y = JUMP_AMOUNT;
-                //play jump sound
-                let _ = self.sounds.jump.play();
+        //F1 toggles the debug overlay regardless of which scene we're in
+        if keycode == KeyCode::F1 {
+            self.debug_overlay.toggle();
+        }
+        match self.scene {
+            Scene::Menu => {
+                //space on the menu starts the run
+                if keycode == KeyCode::Space {
+                    self.scene = Scene::Playing;
+                }
             }
-            else if keycode == KeyCode::LControl || keycode == KeyCode::RControl {
-                //increment the color index by 1 and wrap if it exceeds the length
-                self.player.color_index = (self.player.color_index + 1) % self.colors.len();
-                //assign new color to player 
-                self.player.color = self.colors[self.player.color_index];
-                //play switch sound
-                let _ = self.sounds.switch.play();
+            Scene::Playing => {
+                if keycode == KeyCode::Space {
+                    self.handle_action(Action::Jump, ctx);
+                }
+                else if keycode == KeyCode::LControl || keycode == KeyCode::RControl {
+                    self.handle_action(Action::Switch, ctx);
+                }
+                else if keycode == KeyCode::Tab {
+                    self.handle_action(Action::ToggleSlot, ctx);
+                }
+                else if keycode == KeyCode::Escape {
+                    //freeze pillar/player updates but keep drawing the frozen scene
+                    self.scene = Scene::Paused;
+                }
+            }
+            Scene::Paused => {
+                if keycode == KeyCode::Escape {
+                    self.scene = Scene::Playing;
+                }
+            }
+            Scene::GameOver => {
+                if keycode == KeyCode::Return {
+                    self.handle_action(Action::Restart, ctx);
+                }
             }
         }
-        else if keycode == KeyCode::Return {
-            self.reset(ctx);
+    }
+    //maps a gamepad button press to the same actions the keyboard triggers, so the game
+    //is playable on a controller without a second copy of the jump/switch/restart logic
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: event::Button, _id: event::GamepadId) {
+        match btn {
+            //face button starts the run from the menu, mirroring Space, and jumps otherwise
+            event::Button::South => {
+                if self.scene == Scene::Menu {
+                    self.scene = Scene::Playing;
+                } else {
+                    self.handle_action(Action::Jump, ctx);
+                }
+            }
+            //left bumper cycles the active color slot, mirroring Ctrl
+            event::Button::LeftTrigger => self.handle_action(Action::Switch, ctx),
+            //right bumper toggles which slot that is, mirroring Tab, so a mix target is
+            //reachable with a controller too
+            event::Button::RightTrigger => self.handle_action(Action::ToggleSlot, ctx),
+            //start restarts from the game-over screen
+            event::Button::Start => self.handle_action(Action::Restart, ctx),
+            _ => {}
         }
     }
     //when a key is released
     fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
     }
+    //forward mouse movement/clicks to the debug overlay so its sliders are draggable
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.debug_overlay.handle_mouse_motion(x, y);
+    }
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: input::mouse::MouseButton, _x: f32, _y: f32) {
+        self.debug_overlay.handle_mouse_button(button, true);
+    }
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: input::mouse::MouseButton, _x: f32, _y: f32) {
+        self.debug_overlay.handle_mouse_button(button, false);
+    }
 }
 
 fn main() -> GameResult {
@@ -406,6 +681,10 @@ fn main() -> GameResult {
         path::PathBuf::from("resources")
     };
 
+    //peek at the configured window size before the window (and therefore the Context
+    //that Config::load needs) exists, so resizing it in config.json actually resizes the window
+    let (window_width, window_height) = config::Config::peek_window_size(&resource_dir);
+
     //getting context and event loop
     let (ctx, event_loop) = &mut ContextBuilder::new("ball", "udbhav")
         .add_resource_path(resource_dir)
@@ -413,6 +692,7 @@ fn main() -> GameResult {
             .title("flappy cube color game thing - udbhav")
             .icon("/icon.ico")
         )
+        .window_mode(ggez::conf::WindowMode::default().dimensions(window_width, window_height))
         .build().unwrap();
 
     //building the main state of the game     
@@ -423,6 +703,44 @@ fn main() -> GameResult {
     music.set_repeat(true);
     let _ = music.play();
 
-    //running the main state 
+    //running the main state
     event::run(ctx, event_loop, state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_colors_averages_channels_and_forces_opaque() {
+        let a = Color::new(0.0, 0.2, 1.0, 1.0);
+        let b = Color::new(1.0, 0.6, 0.0, 0.2);
+        let mixed = mix_colors(a, b);
+        assert_eq!(mixed.r, 0.5);
+        assert_eq!(mixed.g, 0.4);
+        assert_eq!(mixed.b, 0.5);
+        assert_eq!(mixed.a, 1.0);
+    }
+
+    #[test]
+    fn mix_colors_clamps_to_the_unit_range() {
+        let mixed = mix_colors(Color::new(-1.0, 2.0, 0.5, 1.0), Color::new(-1.0, 2.0, 0.5, 1.0));
+        assert_eq!(mixed.r, 0.0);
+        assert_eq!(mixed.g, 1.0);
+        assert_eq!(mixed.b, 0.5);
+    }
+
+    #[test]
+    fn colors_match_within_epsilon() {
+        let a = Color::new(0.5, 0.5, 0.5, 1.0);
+        let b = Color::new(0.5 + COLOR_MATCH_EPSILON * 0.5, 0.5, 0.5, 1.0);
+        assert!(colors_match(&a, &b));
+    }
+
+    #[test]
+    fn colors_match_rejects_outside_epsilon() {
+        let a = Color::new(0.5, 0.5, 0.5, 1.0);
+        let b = Color::new(0.5 + COLOR_MATCH_EPSILON * 2.0, 0.5, 0.5, 1.0);
+        assert!(!colors_match(&a, &b));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+//lightweight particle system used for visual feedback on jump/score/crash events
+
+use ggez::graphics::Color;
+use ggez::{graphics, Context, GameResult};
+
+//caps total particle count so the effects stay within the 60 FPS budget
+const MAX_PARTICLES: usize = 400;
+
+//pulls particles down and slows them down slightly every frame
+const GRAVITY: f32 = 0.15;
+const DRAG: f32 = 0.98;
+
+struct Particle {
+    pos: mint::Point2<f32>,
+    vel: mint::Point2<f32>,
+    lifetime: f32,
+    max_lifetime: f32,
+    size: f32,
+    color: Color,
+}
+
+//holds every currently live particle
+pub struct Particles {
+    particles: Vec<Particle>,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles {
+            particles: Vec::new(),
+        }
+    }
+
+    //adds a particle if under the cap, dropping the emission otherwise
+    fn spawn(&mut self, pos: mint::Point2<f32>, vel: mint::Point2<f32>, lifetime: f32, size: f32, color: Color) {
+        if self.particles.len() >= MAX_PARTICLES {
+            return;
+        }
+        self.particles.push(Particle {
+            pos,
+            vel,
+            lifetime,
+            max_lifetime: lifetime,
+            size,
+            color,
+        });
+    }
+
+    //a short upward burst of small squares, used on a Space jump
+    pub fn emit_jump(&mut self, origin: mint::Point2<f32>) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..8 {
+            let vel = mint::Point2 {
+                x: rand::Rng::gen_range(&mut rng, -1.5, 1.5),
+                y: rand::Rng::gen_range(&mut rng, -4.0, -1.0),
+            };
+            self.spawn(origin, vel, 0.4, 4.0, Color::new(0.8, 0.8, 0.8, 1.0));
+        }
+    }
+
+    //a ring of sparkles in the pillar's color, used when a clink scores
+    pub fn emit_clink(&mut self, origin: mint::Point2<f32>, color: Color) {
+        let count = 12;
+        for i in 0..count {
+            let angle = (i as f32 / count as f32) * std::f32::consts::PI * 2.0;
+            let vel = mint::Point2 {
+                x: angle.cos() * 2.5,
+                y: angle.sin() * 2.5,
+            };
+            self.spawn(origin, vel, 0.5, 3.0, color);
+        }
+    }
+
+    //an explosion of the player's color, used on crash
+    pub fn emit_crash(&mut self, origin: mint::Point2<f32>, color: Color) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..30 {
+            let vel = mint::Point2 {
+                x: rand::Rng::gen_range(&mut rng, -5.0, 5.0),
+                y: rand::Rng::gen_range(&mut rng, -5.0, 5.0),
+            };
+            self.spawn(origin, vel, 0.8, 6.0, color);
+        }
+    }
+
+    //integrates position/velocity, applies gravity/drag, and culls dead particles
+    pub fn update(&mut self) {
+        for particle in &mut self.particles {
+            particle.vel.y += GRAVITY;
+            particle.vel.x *= DRAG;
+            particle.vel.y *= DRAG;
+            particle.pos.x += particle.vel.x;
+            particle.pos.y += particle.vel.y;
+            particle.lifetime -= 1.0 / 60.0;
+        }
+        self.particles.retain(|particle| particle.lifetime > 0.0);
+    }
+
+    //renders each particle as a small rectangle faded by its remaining lifetime
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        for particle in &self.particles {
+            let alpha = (particle.lifetime / particle.max_lifetime).max(0.0);
+            let mut color = particle.color;
+            color.a = alpha;
+            let rect = graphics::Rect::new(particle.pos.x, particle.pos.y, particle.size, particle.size);
+            let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+            graphics::draw(ctx, &mesh, graphics::DrawParam::default())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//records a run's Space/Ctrl presses keyed to frame number so a completed attempt can be
+//replayed afterwards as a translucent "ghost" alongside the next one
+
+use ggez::graphics::{self, Color};
+use ggez::{mint, Context, GameResult};
+
+use crate::config::Config;
+
+//how many frames a finished ghost keeps falling before it's dropped, so it doesn't just
+//vanish the instant its last recorded input plays back
+const GHOST_TAIL_FRAMES: u64 = 120;
+
+#[derive(Clone, Copy)]
+pub enum InputKind {
+    Jump,
+    Switch,
+}
+
+#[derive(Clone, Copy)]
+pub struct InputEvent {
+    frame: u64,
+    kind: InputKind,
+}
+
+//accumulates the input stream of the run currently in progress
+pub struct Recorder {
+    frame: u64,
+    events: Vec<InputEvent>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder {
+            frame: 0,
+            events: Vec::new(),
+        }
+    }
+
+    //advances the frame counter that recorded inputs are keyed to; called once per physics tick
+    pub fn tick(&mut self) {
+        self.frame += 1;
+    }
+
+    pub fn record(&mut self, kind: InputKind) {
+        self.events.push(InputEvent { frame: self.frame, kind });
+    }
+
+    //hands off the recorded stream for replay and resets the recorder for the next run
+    pub fn take(&mut self) -> Vec<InputEvent> {
+        self.frame = 0;
+        std::mem::replace(&mut self.events, Vec::new())
+    }
+}
+
+//replays a previous run's input stream against a player-shaped body, drawn translucent
+//alongside the live run; that run is reseeded from the same seed, so it faces the same pillars.
+//holds its own snapshot of the config that was active during the recorded run, so a config
+//edited via the debug overlay (or reloaded) after that run ends doesn't retroactively change
+//how the ghost moves
+pub struct Ghost {
+    config: Config,
+    events: Vec<InputEvent>,
+    cursor: usize,
+    frame: u64,
+    body: graphics::Rect,
+    velocity: mint::Point2<f32>,
+}
+
+impl Ghost {
+    pub fn new(config: Config, events: Vec<InputEvent>) -> Self {
+        Ghost {
+            body: graphics::Rect::new(config.window_width / 2.0, config.window_height / 2.0, 50.0, 50.0),
+            velocity: mint::Point2 { x: 0.0, y: 0.0 },
+            config,
+            events,
+            cursor: 0,
+            frame: 0,
+        }
+    }
+
+    //advances the ghost by one physics tick, replaying whatever inputs were recorded for it,
+    //against the config snapshot it was built with rather than whatever is live now
+    pub fn update(&mut self) {
+        while self.cursor < self.events.len() && self.events[self.cursor].frame == self.frame {
+            if let InputKind::Jump = self.events[self.cursor].kind {
+                self.velocity.y = self.config.jump_amount;
+            }
+            self.cursor += 1;
+        }
+        self.velocity.y += self.config.gravity;
+        self.body.y += self.velocity.y;
+        if self.body.y + self.body.h >= self.config.window_height {
+            self.velocity.y = -self.config.gravity;
+        }
+        self.frame += 1;
+    }
+
+    //true once the recorded stream is exhausted and the tail has played out
+    pub fn finished(&self) -> bool {
+        let last_input_frame = self.events.last().map_or(0, |e| e.frame);
+        self.cursor >= self.events.len() && self.frame > last_input_frame + GHOST_TAIL_FRAMES
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult {
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            self.body,
+            Color::new(0.4, 0.4, 0.4, 0.4),
+        )?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> Config {
+        Config {
+            window_width: 800.0,
+            window_height: 600.0,
+            pillar_gap: 220.0,
+            pillar_distance: 300.0,
+            pillar_width: 80.0,
+            pillar_acceleration: -0.0004,
+            gravity: 1.0,
+            jump_amount: -10.0,
+            initial_pillar_speed: -1.0,
+            palette: vec![[1.0, 1.0, 1.0, 1.0]],
+            sequence: None,
+            rng_seed: None,
+        }
+    }
+
+    #[test]
+    fn ghost_starts_centered_and_stationary() {
+        let ghost = Ghost::new(test_config(), Vec::new());
+        assert_eq!(ghost.body.x, 400.0);
+        assert_eq!(ghost.body.y, 300.0);
+        assert_eq!(ghost.velocity.y, 0.0);
+    }
+
+    #[test]
+    fn ghost_applies_a_jump_on_the_exact_recorded_frame() {
+        let events = vec![InputEvent { frame: 2, kind: InputKind::Jump }];
+        let mut ghost = Ghost::new(test_config(), events);
+
+        ghost.update(); //frame 0 -> 1
+        ghost.update(); //frame 1 -> 2, still falling under gravity
+        assert!(ghost.velocity.y > 0.0);
+
+        ghost.update(); //frame 2's jump fires here, then gravity is applied on top of it
+        assert_eq!(ghost.velocity.y, -10.0 + 1.0);
+    }
+
+    #[test]
+    fn ghost_fires_a_recorded_jump_only_once() {
+        //once a recorded jump has fired on its own frame, it is never replayed on a later one
+        let events = vec![InputEvent { frame: 0, kind: InputKind::Jump }];
+        let mut ghost = Ghost::new(test_config(), events);
+
+        ghost.update(); //consumes frame 0's jump, then gravity applies
+        assert_eq!(ghost.velocity.y, -10.0 + 1.0);
+
+        ghost.update(); //no more recorded inputs: falls under gravity alone
+        assert_eq!(ghost.velocity.y, -10.0 + 1.0 + 1.0);
+    }
+
+    #[test]
+    fn ghost_is_not_finished_until_the_tail_has_played_out() {
+        let events = vec![InputEvent { frame: 0, kind: InputKind::Jump }];
+        let mut ghost = Ghost::new(test_config(), events);
+
+        //one update for the input's own frame, then GHOST_TAIL_FRAMES more of tail
+        for _ in 0..=GHOST_TAIL_FRAMES {
+            assert!(!ghost.finished());
+            ghost.update();
+        }
+        assert!(ghost.finished());
+    }
+}
@@ -0,0 +1,9 @@
+//the scenes the game can be in; MainState::update/draw dispatch on this instead of a bool
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Scene {
+    Menu,
+    Playing,
+    Paused,
+    GameOver,
+}
@@ -0,0 +1,296 @@
+//software synth used to generate sound effects at runtime instead of shipping .wav assets
+
+use std::f32::consts::PI;
+
+use ggez::audio::{SoundData, SoundSource, Source};
+use ggez::{Context, GameResult};
+
+use rand::Rng;
+
+//samples per second used when rendering a voice to a buffer
+const SAMPLE_RATE: u32 = 44100;
+
+//envelope level below which a voice is considered finished
+const RETIRE_THRESHOLD: f32 = 0.001;
+
+//pentatonic scale used to pick a pitch for the switch event from color_index
+const PENTATONIC: [f32; 5] = [261.63, 293.66, 329.63, 392.00, 440.00];
+
+//the waveform shape produced by a voice's oscillator
+#[derive(Clone, Copy)]
+enum Waveform {
+    Sine,
+    Square,
+    Noise,
+}
+
+//a single triggerable oscillator + attack-decay envelope
+struct Voice {
+    waveform: Waveform,
+    attack: f32,
+    decay: f32,
+    //fraction of the triggered frequency the pitch glides towards by the time the envelope
+    //fully decays away; 1.0 means the pitch holds steady, as it does for every voice but jump
+    pitch_end_ratio: f32,
+    freq: f32,
+    amplitude: f32,
+    phase: f32,
+    env: f32,
+    active: bool,
+}
+
+impl Voice {
+    fn new(waveform: Waveform, attack: f32, decay: f32, pitch_end_ratio: f32) -> Self {
+        Voice {
+            waveform,
+            attack,
+            decay,
+            pitch_end_ratio,
+            freq: 0.0,
+            amplitude: 0.0,
+            phase: 0.0,
+            env: 0.0,
+            active: false,
+        }
+    }
+
+    //resets phase/envelope to zero and assigns the target frequency for this trigger
+    fn trigger(&mut self, freq: f32, amplitude: f32) {
+        self.freq = freq;
+        self.amplitude = amplitude;
+        self.phase = 0.0;
+        self.env = 0.0;
+        self.active = true;
+    }
+
+    //how long the decay tail takes to fall below RETIRE_THRESHOLD, given this voice's decay constant
+    fn decay_tail(&self) -> f32 {
+        if self.decay > 0.0 && self.decay < 1.0 {
+            RETIRE_THRESHOLD.ln() / self.decay.ln()
+        } else {
+            0.0
+        }
+    }
+
+    //total lifetime of one trigger: the attack ramp plus the decay tail
+    fn life(&self) -> f32 {
+        self.attack + self.decay_tail()
+    }
+
+    //advances the envelope by dt and returns this voice's contribution to the current sample
+    fn sample(&mut self, t: f32, dt: f32) -> f32 {
+        if !self.active {
+            return 0.0;
+        }
+        self.env = if t < self.attack {
+            t / self.attack
+        } else {
+            self.decay.powf(t - self.attack)
+        };
+        if self.env < RETIRE_THRESHOLD {
+            self.active = false;
+            return 0.0;
+        }
+        //glide the pitch towards pitch_end_ratio across the decay tail, not during the attack
+        let glide = if t <= self.attack {
+            0.0
+        } else {
+            ((t - self.attack) / self.decay_tail()).min(1.0)
+        };
+        let freq_now = self.freq * (1.0 + (self.pitch_end_ratio - 1.0) * glide);
+        let osc = match self.waveform {
+            Waveform::Sine => self.phase.sin(),
+            Waveform::Square => if self.phase.sin() >= 0.0 { 1.0 } else { -1.0 },
+            Waveform::Noise => rand::thread_rng().gen_range(-1.0, 1.0),
+        };
+        self.phase += 2.0 * PI * freq_now * dt;
+        if self.phase > 2.0 * PI {
+            self.phase -= 2.0 * PI;
+        }
+        self.amplitude * self.env * osc
+    }
+}
+
+//the events the synth can be triggered with, each mapped to one voice
+pub enum Event {
+    Jump,
+    Switch { color_index: usize },
+    Clink,
+    Crash,
+}
+
+//holds the small set of voices used to realize the jump/switch/clink/crash effects
+pub struct Synth {
+    jump: Voice,
+    switch: Voice,
+    clink: Voice,
+    crash: Voice,
+    //one-shot Sources currently playing; kept alive here so rodio doesn't stop them on drop
+    playing: Vec<Source>,
+}
+
+impl Synth {
+    pub fn new() -> Self {
+        Synth {
+            //jump glides down an octave over its decay tail, giving it a descending pitch
+            jump: Voice::new(Waveform::Sine, 0.01, 0.0003, 0.5),
+            switch: Voice::new(Waveform::Square, 0.005, 0.0006, 1.0),
+            clink: Voice::new(Waveform::Sine, 0.001, 0.0002, 1.0),
+            crash: Voice::new(Waveform::Noise, 0.001, 0.00008, 1.0),
+            playing: Vec::new(),
+        }
+    }
+
+    //resets the voice for the given event and assigns it a target frequency
+    pub fn trigger(&mut self, event: Event) {
+        match event {
+            //jump gets a low descending pitch via its decay envelope
+            Event::Jump => self.jump.trigger(220.0, 0.5),
+            //switch picks a pitch from the pentatonic table indexed by color_index
+            Event::Switch { color_index } => {
+                let freq = PENTATONIC[color_index % PENTATONIC.len()];
+                self.switch.trigger(freq, 0.4)
+            }
+            //clink is a bright high ping
+            Event::Clink => self.clink.trigger(1600.0, 0.35),
+            //crash is a short noise burst
+            Event::Crash => self.crash.trigger(120.0, 0.6),
+        }
+    }
+
+    //sums amplitude * env * osc across all active voices for one frame and advances them
+    fn next_frame(&mut self, t: f32, dt: f32) -> f32 {
+        self.jump.sample(t, dt)
+            + self.switch.sample(t, dt)
+            + self.clink.sample(t, dt)
+            + self.crash.sample(t, dt)
+    }
+
+    //how long the currently active voices need to decay below the retirement threshold
+    fn active_duration(&self) -> f32 {
+        [&self.jump, &self.switch, &self.clink, &self.crash]
+            .iter()
+            .filter(|voice| voice.active)
+            .map(|voice| voice.life())
+            .fold(0.0_f32, f32::max)
+    }
+
+    //renders duration seconds of the currently triggered voices into an interleaved stereo buffer
+    fn render(&mut self, duration: f32) -> Vec<u8> {
+        let dt = 1.0 / SAMPLE_RATE as f32;
+        let num_samples = (duration * SAMPLE_RATE as f32) as usize;
+        let mut buffer = Vec::with_capacity(num_samples * 4);
+        for i in 0..num_samples {
+            let t = i as f32 * dt;
+            let sample = self.next_frame(t, dt);
+            let clamped = (sample.max(-1.0).min(1.0) * i16::MAX as f32) as i16;
+            //duplicate to both stereo channels
+            buffer.extend_from_slice(&clamped.to_le_bytes());
+            buffer.extend_from_slice(&clamped.to_le_bytes());
+        }
+        buffer
+    }
+
+    //wraps raw interleaved 16-bit stereo PCM in a minimal WAV header, since ggez decodes
+    //Source data through a format-sniffing decoder that expects a real container
+    fn wrap_wav(pcm: &[u8]) -> Vec<u8> {
+        let channels: u16 = 2;
+        let bits_per_sample: u16 = 16;
+        let byte_rate = SAMPLE_RATE * channels as u32 * (bits_per_sample / 8) as u32;
+        let block_align = channels * (bits_per_sample / 8);
+        let data_len = pcm.len() as u32;
+
+        let mut wav = Vec::with_capacity(44 + pcm.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); //PCM
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.extend_from_slice(pcm);
+        wav
+    }
+
+    //renders however long the voices triggered this frame need to fully decay, and plays
+    //the result back through ggez; does nothing if nothing was triggered
+    pub fn play_triggered(&mut self, ctx: &mut Context) -> GameResult {
+        //drop one-shots that have already finished playing, so the vec doesn't grow forever
+        self.playing.retain(|source| source.playing());
+        let duration = self.active_duration();
+        if duration <= 0.0 {
+            return Ok(());
+        }
+        let pcm = self.render(duration);
+        //every active voice has now been rendered through its whole life, so retire them all;
+        //otherwise the longest one (whose last sample sits just above RETIRE_THRESHOLD) would be
+        //rendered again on the next call
+        for voice in [&mut self.jump, &mut self.switch, &mut self.clink, &mut self.crash].iter_mut() {
+            voice.active = false;
+        }
+        let data = SoundData::from(Self::wrap_wav(&pcm));
+        let mut source = Source::from_data(ctx, data)?;
+        source.play()?;
+        //keep the Source alive until it's done playing, otherwise rodio stops it on drop
+        self.playing.push(source);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_wav_writes_a_44_byte_header_with_the_right_sizes() {
+        let pcm = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let wav = Synth::wrap_wav(&pcm);
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + pcm.len() as u32);
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(&wav[12..16], b"fmt ");
+        assert_eq!(u32::from_le_bytes(wav[16..20].try_into().unwrap()), 16);
+        assert_eq!(u16::from_le_bytes(wav[20..22].try_into().unwrap()), 1); //PCM
+        assert_eq!(u16::from_le_bytes(wav[22..24].try_into().unwrap()), 2); //channels
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), pcm.len() as u32);
+        assert_eq!(&wav[44..], &pcm);
+    }
+
+    #[test]
+    fn voice_is_silent_and_inactive_until_triggered() {
+        let mut voice = Voice::new(Waveform::Sine, 0.01, 0.5, 1.0);
+        assert_eq!(voice.sample(0.0, 1.0 / SAMPLE_RATE as f32), 0.0);
+        assert!(!voice.active);
+    }
+
+    #[test]
+    fn voice_ramps_up_through_attack_then_decays_and_retires() {
+        let mut voice = Voice::new(Waveform::Sine, 0.01, 0.5, 1.0);
+        voice.trigger(220.0, 1.0);
+        let dt = 1.0 / SAMPLE_RATE as f32;
+
+        //partway through the attack ramp, the envelope should be a fraction below 1
+        voice.sample(0.005, dt);
+        assert!(voice.env > 0.0 && voice.env < 1.0);
+
+        //well past decay_tail(), the voice should have crossed RETIRE_THRESHOLD and retired
+        let tail = voice.decay_tail();
+        voice.sample(voice.attack + tail + 0.01, dt);
+        assert!(!voice.active);
+    }
+
+    #[test]
+    fn voice_life_is_attack_plus_decay_tail() {
+        let voice = Voice::new(Waveform::Sine, 0.01, 0.5, 1.0);
+        assert_eq!(voice.life(), voice.attack + voice.decay_tail());
+    }
+}